@@ -64,6 +64,15 @@ impl<ID> SpacesMessage<ID, TestConditions> for TestMessage<ID> {
     }
 }
 
+impl<ID> p2panda_spaces::sync::SequencedMessage for TestMessage<ID>
+where
+    ID: SpaceId,
+{
+    fn seq_num(&self) -> u64 {
+        self.seq_num
+    }
+}
+
 #[derive(Debug)]
 pub struct TestForge<ID> {
     next_seq_num: SeqNum,
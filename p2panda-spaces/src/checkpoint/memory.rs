@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::hash::Hash;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::checkpoint::{CheckpointStore, EncryptedCheckpoint};
+
+/// Reference [`CheckpointStore`] used by tests: checkpoints are kept in a
+/// `Vec` per space, oldest first, so `prune_checkpoints` is a simple
+/// truncate from the front.
+#[derive(Debug, Clone)]
+pub struct MemoryCheckpointStore<I> {
+    inner: Arc<RwLock<HashMap<I, Vec<EncryptedCheckpoint<I>>>>>,
+}
+
+impl<I> MemoryCheckpointStore<I> {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+}
+
+impl<I> Default for MemoryCheckpointStore<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I> CheckpointStore<I> for MemoryCheckpointStore<I>
+where
+    I: Clone + Eq + Hash + Send + Sync,
+{
+    type Error = Infallible;
+
+    async fn latest_checkpoint(
+        &self,
+        space: &I,
+    ) -> Result<Option<EncryptedCheckpoint<I>>, Self::Error> {
+        let inner = self.inner.read().await;
+        Ok(inner.get(space).and_then(|checkpoints| checkpoints.last()).cloned())
+    }
+
+    async fn put_checkpoint(&self, checkpoint: EncryptedCheckpoint<I>) -> Result<(), Self::Error> {
+        let mut inner = self.inner.write().await;
+        inner
+            .entry(checkpoint.space.clone())
+            .or_default()
+            .push(checkpoint);
+        Ok(())
+    }
+
+    async fn checkpoints(&self, space: &I) -> Result<Vec<EncryptedCheckpoint<I>>, Self::Error> {
+        let inner = self.inner.read().await;
+        Ok(inner
+            .get(space)
+            .map(|checkpoints| checkpoints.iter().rev().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn prune_checkpoints(&self, space: &I, keep_last: usize) -> Result<(), Self::Error> {
+        let mut inner = self.inner.write().await;
+        if let Some(checkpoints) = inner.get_mut(space) {
+            let drop_count = checkpoints.len().saturating_sub(keep_last);
+            checkpoints.drain(0..drop_count);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,547 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Checkpoint-and-compact the operation log.
+//!
+//! [`MessageStore`] keeps every applied operation forever, so loading a
+//! long-lived space replays its full history. A [`Checkpointer`] lets the
+//! caller (after applying an operation) serialize the
+//! fully materialized `SpaceState`/`AuthGroupState` into an encrypted
+//! [`EncryptedCheckpoint`] tagged with the causal heads it covers, store it
+//! via [`CheckpointStore`], and then prune operations the checkpoint has
+//! made redundant.
+//!
+//! This module only knows how to take and load checkpoints; it does not
+//! compute which operations are safe to prune. Causal safety (never pruning
+//! an operation still referenced by a concurrent branch the `StrongRemoveResolver`
+//! hasn't merged yet) depends on the auth group's ordering state, which only
+//! the caller holds, so [`compact`] takes the prunable set as
+//! an explicit argument rather than deriving it here.
+
+mod memory;
+
+pub use memory::MemoryCheckpointStore;
+
+use std::collections::BTreeSet;
+
+use p2panda_auth::traits::Conditions;
+use serde::Serialize;
+
+use crate::OperationId;
+use crate::space::SpaceState;
+use crate::traits::{AuthStore, MessageStore, SpaceStore};
+use crate::types::AuthGroupState;
+
+/// The causal heads included in a checkpoint: every operation reachable from
+/// this set (by following dependencies backwards) is represented in the
+/// checkpointed state.
+pub type Heads = BTreeSet<OperationId>;
+
+/// Plaintext payload encrypted into an [`EncryptedCheckpoint`].
+#[derive(Clone, Debug, Serialize, serde::Deserialize)]
+#[serde(bound = "")]
+struct CheckpointPayload<I, M, C>
+where
+    C: Conditions,
+{
+    space: SpaceState<I, M, C>,
+    auth: AuthGroupState<C>,
+}
+
+/// A checkpoint as it is stored and transported: everything but the
+/// ciphertext is kept in the clear so a store can index and prune
+/// checkpoints without holding the decryption key.
+#[derive(Clone, Debug, Serialize, serde::Deserialize)]
+pub struct EncryptedCheckpoint<I> {
+    pub space: I,
+    pub heads: Heads,
+    /// Monotonically increasing per space; breaks ties between checkpoints
+    /// that happen to share the same heads and orders `keep_last` pruning.
+    pub sequence: u64,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Symmetric encryption for checkpoint payloads, kept abstract so this
+/// module does not dictate which AEAD construction a deployment uses.
+pub trait CheckpointCipher {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Self::Error>;
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Self::Error>;
+}
+
+/// Where checkpoints are kept. Distinct from [`crate::store::BlobStore`] so a
+/// deployment can route checkpoints to different storage (e.g. smaller,
+/// hotter storage) than the raw operation log.
+pub trait CheckpointStore<I> {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    async fn latest_checkpoint(
+        &self,
+        space: &I,
+    ) -> Result<Option<EncryptedCheckpoint<I>>, Self::Error>;
+
+    async fn put_checkpoint(&self, checkpoint: EncryptedCheckpoint<I>) -> Result<(), Self::Error>;
+
+    /// All checkpoints kept for `space`, most recent first.
+    async fn checkpoints(&self, space: &I) -> Result<Vec<EncryptedCheckpoint<I>>, Self::Error>;
+
+    /// Drop all but the `keep_last` most recent checkpoints for `space`,
+    /// so a botched checkpoint can still be rolled back from.
+    async fn prune_checkpoints(&self, space: &I, keep_last: usize) -> Result<(), Self::Error>;
+}
+
+/// A [`MessageStore`] that can also drop operations once a checkpoint has
+/// made them redundant. Kept as a separate trait rather than extending
+/// [`MessageStore`] itself, since most callers (sync, replay) never need to
+/// delete an operation and should not have to implement a no-op for it.
+pub trait PrunableMessageStore<M>: MessageStore<M> {
+    async fn remove_message(&self, id: &OperationId) -> Result<(), Self::Error>;
+}
+
+/// How often to take a checkpoint, expressed as a count of applied
+/// operations rather than wall-clock time so behavior stays deterministic
+/// across peers.
+#[derive(Clone, Copy, Debug)]
+pub struct CheckpointPolicy {
+    pub every_n_operations: u64,
+}
+
+impl CheckpointPolicy {
+    pub fn is_due(&self, applied_since_last_checkpoint: u64) -> bool {
+        applied_since_last_checkpoint >= self.every_n_operations
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CheckpointError<StoreErr, CheckpointErr, CipherErr> {
+    #[error("space/auth store error: {0}")]
+    Store(StoreErr),
+
+    #[error("checkpoint store error: {0}")]
+    CheckpointStore(CheckpointErr),
+
+    #[error("checkpoint cipher error: {0}")]
+    Cipher(CipherErr),
+
+    #[error("failed to encode checkpoint payload: {0}")]
+    Encode(#[from] ciborium::ser::Error<std::io::Error>),
+
+    #[error("space {0:?} has no state to checkpoint")]
+    NoSuchSpace(String),
+}
+
+/// Drives taking and restoring checkpoints for a single store. `Store` must
+/// provide the materialized state; `Ck` persists the encrypted checkpoints;
+/// `Cipher` encrypts/decrypts the payload.
+pub struct Checkpointer<Store, Ck, Cipher> {
+    store: Store,
+    checkpoints: Ck,
+    cipher: Cipher,
+    /// Number of past checkpoints kept per space for rollback.
+    keep_last: usize,
+}
+
+impl<Store, Ck, Cipher> Checkpointer<Store, Ck, Cipher> {
+    pub fn new(store: Store, checkpoints: Ck, cipher: Cipher, keep_last: usize) -> Self {
+        Self {
+            store,
+            checkpoints,
+            cipher,
+            keep_last,
+        }
+    }
+}
+
+impl<Store, Ck, Cipher> Checkpointer<Store, Ck, Cipher>
+where
+    Ck: CheckpointStore<String>,
+    Cipher: CheckpointCipher,
+{
+    /// Materialize `space`'s current state, encrypt it and store it as the
+    /// new latest checkpoint tagged with `heads`, then prune all but the
+    /// last `keep_last` checkpoints.
+    pub async fn checkpoint<I, M, C, E>(
+        &self,
+        space: &I,
+        heads: Heads,
+        sequence: u64,
+    ) -> Result<(), CheckpointError<E, Ck::Error, Cipher::Error>>
+    where
+        I: Clone + std::fmt::Debug + Into<String>,
+        M: Clone + Send + Sync,
+        C: Conditions + Send + Sync,
+        E: std::error::Error + Send + Sync + 'static,
+        Store: SpaceStore<I, M, C, Error = E> + AuthStore<C, Error = E>,
+    {
+        let space_state = self
+            .store
+            .space(space)
+            .await
+            .map_err(CheckpointError::Store)?
+            .ok_or_else(|| CheckpointError::NoSuchSpace(format!("{space:?}")))?;
+        let auth = self.store.auth().await.map_err(CheckpointError::Store)?;
+
+        let payload = CheckpointPayload {
+            space: space_state,
+            auth,
+        };
+        encrypt_and_store(
+            &self.checkpoints,
+            &self.cipher,
+            space.clone().into(),
+            heads,
+            sequence,
+            self.keep_last,
+            &payload,
+        )
+        .await
+        .map_err(|err| match err {
+            EncryptAndStoreError::Encode(err) => CheckpointError::Encode(err),
+            EncryptAndStoreError::Store(err) => CheckpointError::CheckpointStore(err),
+            EncryptAndStoreError::Cipher(err) => CheckpointError::Cipher(err),
+        })
+    }
+
+    /// Check `policy`, and if a checkpoint is due, take one and compact the
+    /// log in a single call: it bundles the policy check, [`Self::checkpoint`]
+    /// and [`compact`] so a call site doesn't have to reassemble them
+    /// itself, and skips all three (including the message-store round trip
+    /// `compact` would otherwise make) when a checkpoint isn't due.
+    /// Returns whether a checkpoint was actually taken.
+    ///
+    /// Nothing in this crate calls it; `Manager` is not part of this source
+    /// tree, so no checkpoint is taken after an applied operation unless the
+    /// caller makes this call itself.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn maybe_checkpoint<I, M, C, E, Msgs>(
+        &self,
+        space: &I,
+        heads: Heads,
+        sequence: u64,
+        policy: &CheckpointPolicy,
+        applied_since_last_checkpoint: u64,
+        messages: &Msgs,
+        dominated: &Heads,
+        excluded: &Heads,
+    ) -> Result<bool, CheckpointError<E, Ck::Error, Cipher::Error>>
+    where
+        I: Clone + std::fmt::Debug + Into<String>,
+        M: Clone + Send + Sync,
+        C: Conditions + Send + Sync,
+        E: std::error::Error + Send + Sync + 'static,
+        Store: SpaceStore<I, M, C, Error = E> + AuthStore<C, Error = E>,
+        Msgs: PrunableMessageStore<M, Error = E>,
+    {
+        if !policy.is_due(applied_since_last_checkpoint) {
+            return Ok(false);
+        }
+        self.checkpoint::<I, M, C, E>(space, heads, sequence).await?;
+        compact(messages, dominated, excluded)
+            .await
+            .map_err(CheckpointError::Store)?;
+        Ok(true)
+    }
+
+    /// Load the latest checkpoint for `space` and decrypt/deserialize it.
+    /// Returns `Ok(None)` both when there is no checkpoint yet and when the
+    /// latest one fails to decrypt or decode, so the caller's only
+    /// remaining option in either case is a full replay from the start of
+    /// the log.
+    pub async fn restore<I, M, C>(
+        &self,
+        space: &I,
+    ) -> Result<Option<(SpaceState<I, M, C>, AuthGroupState<C>, Heads)>, Ck::Error>
+    where
+        I: Clone + Into<String>,
+        M: Clone,
+        C: Conditions,
+    {
+        let Some((payload, heads)) = fetch_and_decrypt::<CheckpointPayload<I, M, C>, _, _>(
+            &self.checkpoints,
+            &self.cipher,
+            &space.clone().into(),
+        )
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        Ok(Some((payload.space, payload.auth, heads)))
+    }
+
+}
+
+/// Errors from [`encrypt_and_store`], kept separate from [`CheckpointError`]
+/// so this helper (and its tests) don't need a `SpaceStore`/`AuthStore`
+/// error type `E` to name a result type.
+#[derive(Debug, thiserror::Error)]
+enum EncryptAndStoreError<CheckpointErr, CipherErr> {
+    #[error("failed to encode checkpoint payload: {0}")]
+    Encode(#[from] ciborium::ser::Error<std::io::Error>),
+
+    #[error("checkpoint store error: {0}")]
+    Store(CheckpointErr),
+
+    #[error("checkpoint cipher error: {0}")]
+    Cipher(CipherErr),
+}
+
+/// Serialize `payload`, encrypt it and store it as `space`'s new latest
+/// checkpoint, then prune all but the last `keep_last` checkpoints. The
+/// actual work behind [`Checkpointer::checkpoint`], pulled out as a free
+/// function generic over the payload type so it can be exercised with a
+/// trivial payload in tests instead of needing a real `SpaceState`/
+/// `AuthGroupState` (which nothing in this tree can construct).
+#[allow(clippy::too_many_arguments)]
+async fn encrypt_and_store<P, Ck, Cipher>(
+    checkpoints: &Ck,
+    cipher: &Cipher,
+    space: String,
+    heads: Heads,
+    sequence: u64,
+    keep_last: usize,
+    payload: &P,
+) -> Result<(), EncryptAndStoreError<Ck::Error, Cipher::Error>>
+where
+    P: Serialize,
+    Ck: CheckpointStore<String>,
+    Cipher: CheckpointCipher,
+{
+    let mut plaintext = Vec::new();
+    ciborium::into_writer(payload, &mut plaintext)?;
+    let ciphertext = cipher
+        .encrypt(&plaintext)
+        .map_err(EncryptAndStoreError::Cipher)?;
+
+    checkpoints
+        .put_checkpoint(EncryptedCheckpoint {
+            space: space.clone(),
+            heads,
+            sequence,
+            ciphertext,
+        })
+        .await
+        .map_err(EncryptAndStoreError::Store)?;
+    checkpoints
+        .prune_checkpoints(&space, keep_last)
+        .await
+        .map_err(EncryptAndStoreError::Store)
+}
+
+/// Load `space`'s latest checkpoint and decrypt/deserialize it. Returns
+/// `Ok(None)` both when there is no checkpoint yet and when the latest one
+/// fails to decrypt or decode. The actual work behind
+/// [`Checkpointer::restore`], pulled out for the same reason as
+/// [`encrypt_and_store`].
+async fn fetch_and_decrypt<P, Ck, Cipher>(
+    checkpoints: &Ck,
+    cipher: &Cipher,
+    space: &str,
+) -> Result<Option<(P, Heads)>, Ck::Error>
+where
+    P: serde::de::DeserializeOwned,
+    Ck: CheckpointStore<String>,
+    Cipher: CheckpointCipher,
+{
+    let Some(checkpoint) = checkpoints.latest_checkpoint(&space.to_string()).await? else {
+        return Ok(None);
+    };
+
+    let Ok(plaintext) = cipher.decrypt(&checkpoint.ciphertext) else {
+        return Ok(None);
+    };
+    let Ok(payload) = ciborium::from_reader::<P, _>(plaintext.as_slice()) else {
+        return Ok(None);
+    };
+
+    Ok(Some((payload, checkpoint.heads)))
+}
+
+/// Remove every operation in `dominated` that is not also present in
+/// `excluded` (operations still reachable from an unmerged concurrent
+/// branch). Call only after [`Checkpointer::checkpoint`] has succeeded for
+/// the heads `dominated` was computed against.
+///
+/// A free function rather than a `Checkpointer` method: pruning only needs
+/// the message store, not the space/auth store or the cipher, and callers
+/// (e.g. `Manager`) that already hold their own `MessageStore` reference
+/// shouldn't have to route it through a `Checkpointer` to use it.
+pub async fn compact<M, S>(messages: &S, dominated: &Heads, excluded: &Heads) -> Result<(), S::Error>
+where
+    M: Send + Sync,
+    S: PrunableMessageStore<M>,
+{
+    for id in dominated.difference(excluded) {
+        messages.remove_message(id).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+    use std::sync::Arc;
+
+    use tokio::sync::RwLock;
+
+    use super::*;
+
+    #[test]
+    fn checkpoint_is_due_once_threshold_reached() {
+        let policy = CheckpointPolicy {
+            every_n_operations: 100,
+        };
+        assert!(!policy.is_due(99));
+        assert!(policy.is_due(100));
+        assert!(policy.is_due(150));
+    }
+
+    /// Bare-bones [`PrunableMessageStore`] recording which ids were removed,
+    /// so [`compact`]'s pruning decision can be checked without a real
+    /// backend.
+    #[derive(Default)]
+    struct RecordingMessageStore {
+        removed: Arc<RwLock<Vec<OperationId>>>,
+    }
+
+    impl crate::traits::MessageStore<()> for RecordingMessageStore {
+        type Error = Infallible;
+
+        async fn message(&self, _id: &OperationId) -> Result<Option<()>, Self::Error> {
+            Ok(None)
+        }
+
+        async fn set_message(&self, _id: &OperationId, _message: &()) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    impl PrunableMessageStore<()> for RecordingMessageStore {
+        async fn remove_message(&self, id: &OperationId) -> Result<(), Self::Error> {
+            self.removed.write().await.push(*id);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn compact_prunes_dominated_but_keeps_excluded() {
+        let a = OperationId::new(b"a");
+        let b = OperationId::new(b"b");
+        let c = OperationId::new(b"c");
+
+        let dominated = Heads::from([a, b, c]);
+        // `b` is still reachable from an unmerged concurrent branch, so it
+        // must survive compaction even though it's dominated by the new
+        // checkpoint.
+        let excluded = Heads::from([b]);
+
+        let store = RecordingMessageStore::default();
+        compact(&store, &dominated, &excluded).await.unwrap();
+
+        let removed = store.removed.read().await.clone();
+        assert_eq!(removed.len(), 2);
+        assert!(removed.contains(&a));
+        assert!(removed.contains(&c));
+        assert!(!removed.contains(&b));
+    }
+
+    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct TestPayload {
+        value: String,
+    }
+
+    /// Not a real AEAD - XORs with a fixed key stream and appends a one-byte
+    /// XOR checksum of the plaintext - but it is enough to exercise
+    /// `encrypt_and_store`/`fetch_and_decrypt` without pulling in a real
+    /// cipher dependency: like a real AEAD's authentication tag, the
+    /// checksum makes garbage ciphertext fail to decrypt.
+    struct XorCipher {
+        key: [u8; 4],
+    }
+
+    #[derive(Debug, thiserror::Error)]
+    #[error("ciphertext checksum does not match")]
+    struct XorCipherError;
+
+    impl XorCipher {
+        fn xor(&self, bytes: &[u8]) -> Vec<u8> {
+            bytes
+                .iter()
+                .enumerate()
+                .map(|(i, byte)| byte ^ self.key[i % self.key.len()])
+                .collect()
+        }
+    }
+
+    fn checksum(bytes: &[u8]) -> u8 {
+        bytes.iter().fold(0, |acc, byte| acc ^ byte)
+    }
+
+    impl CheckpointCipher for XorCipher {
+        type Error = XorCipherError;
+
+        fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            let mut ciphertext = self.xor(plaintext);
+            ciphertext.push(checksum(plaintext));
+            Ok(ciphertext)
+        }
+
+        fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Self::Error> {
+            let (tag, body) = ciphertext.split_last().ok_or(XorCipherError)?;
+            let plaintext = self.xor(body);
+            if checksum(&plaintext) != *tag {
+                return Err(XorCipherError);
+            }
+            Ok(plaintext)
+        }
+    }
+
+    #[tokio::test]
+    async fn encrypt_and_store_then_fetch_and_decrypt_round_trips() {
+        let checkpoints = MemoryCheckpointStore::default();
+        let cipher = XorCipher { key: [1, 2, 3, 4] };
+        let payload = TestPayload {
+            value: "a space's materialized state".to_string(),
+        };
+        let heads = Heads::from([OperationId::new(b"a")]);
+
+        encrypt_and_store(&checkpoints, &cipher, "space".to_string(), heads.clone(), 1, 10, &payload)
+            .await
+            .unwrap();
+
+        let (restored, restored_heads) =
+            fetch_and_decrypt::<TestPayload, _, _>(&checkpoints, &cipher, "space")
+                .await
+                .unwrap()
+                .expect("checkpoint was just stored");
+        assert_eq!(restored, payload);
+        assert_eq!(restored_heads, heads);
+    }
+
+    #[tokio::test]
+    async fn fetch_and_decrypt_returns_none_for_garbage_ciphertext() {
+        let checkpoints = MemoryCheckpointStore::default();
+        let cipher = XorCipher { key: [1, 2, 3, 4] };
+
+        checkpoints
+            .put_checkpoint(EncryptedCheckpoint {
+                space: "space".to_string(),
+                heads: Heads::new(),
+                sequence: 1,
+                // The trailing byte is not the checksum of the rest, so
+                // `XorCipher::decrypt` itself errors - the same
+                // "undecryptable checkpoint" case a real AEAD's tag check
+                // would hit on corrupt ciphertext.
+                ciphertext: vec![0xff; 3],
+            })
+            .await
+            .unwrap();
+
+        let restored =
+            fetch_and_decrypt::<TestPayload, _, _>(&checkpoints, &cipher, "space")
+                .await
+                .unwrap();
+        assert_eq!(restored, None);
+    }
+}
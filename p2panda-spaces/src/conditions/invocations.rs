@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::OperationId;
+
+/// Tracks how many times each grant has been invoked, keyed by the
+/// operation id that produced the grant ([`ScopedConditions::granted_by`](crate::conditions::ScopedConditions::granted_by)).
+/// [`ScopedConditions::resolve`](crate::conditions::ScopedConditions::resolve)
+/// reads the current count; the caller increments it with
+/// `record_invocation` after accepting an operation authored under the
+/// grant.
+pub trait InvocationStore {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    async fn invocations(&self, grant: &OperationId) -> Result<u32, Self::Error>;
+
+    async fn record_invocation(&self, grant: &OperationId) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MemoryInvocationStore {
+    counts: Arc<RwLock<HashMap<OperationId, u32>>>,
+}
+
+impl MemoryInvocationStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl InvocationStore for MemoryInvocationStore {
+    type Error = Infallible;
+
+    async fn invocations(&self, grant: &OperationId) -> Result<u32, Self::Error> {
+        let counts = self.counts.read().await;
+        Ok(counts.get(grant).copied().unwrap_or(0))
+    }
+
+    async fn record_invocation(&self, grant: &OperationId) -> Result<(), Self::Error> {
+        let mut counts = self.counts.write().await;
+        *counts.entry(*grant).or_insert(0) += 1;
+        Ok(())
+    }
+}
@@ -0,0 +1,188 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Time- and capability-scoped [`Conditions`].
+//!
+//! `TestConditions` is an empty marker, so an `Access` grant carries no
+//! semantics and stays valid forever until explicitly revoked.
+//! [`ScopedConditions`] gives a grant a validity window and an optional
+//! invocation cap, so e.g. a guest editor can be granted access that
+//! auto-downgrades after a deadline, without an admin needing to come back
+//! online to issue the removal.
+//!
+//! Expiry must evaluate identically on every peer, so it is checked against
+//! [`LogicalTime`] - the auth group's causal position, the same concept
+//! `p2panda_encryption::key_bundle::Lifetime` uses for pre-key validity,
+//! applied here to operations instead of keys - never the wall clock. Two
+//! peers applying the same operations in the same order must reach the same
+//! verdict regardless of when each of them actually runs.
+
+mod invocations;
+
+pub use invocations::{InvocationStore, MemoryInvocationStore};
+
+use p2panda_auth::traits::Conditions;
+use serde::{Deserialize, Serialize};
+
+use crate::OperationId;
+
+/// A point in the auth group's causal order, not wall-clock time.
+/// Concretely, the sequence number `AuthOrderer` assigns an operation once
+/// it has been causally ordered. Comparing two `LogicalTime`s only makes
+/// sense within the same space.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LogicalTime(pub u64);
+
+/// Time- and capability-scoped conditions on an `Access` grant.
+#[derive(Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct ScopedConditions {
+    /// The operation that produced this grant. Two grants with an
+    /// otherwise identical window are still distinct `ScopedConditions`
+    /// values because of this field, so a re-grant issued after an earlier
+    /// one lapsed is never conflated with the lapsed grant when the
+    /// `StrongRemoveResolver` orders adds against removes.
+    pub granted_by: OperationId,
+    /// Inclusive: the grant is inactive before this logical time. `None`
+    /// means active from the start of the space.
+    pub not_before: Option<LogicalTime>,
+    /// Exclusive: the grant is inactive at and after this logical time.
+    /// `None` means it never expires by time.
+    pub not_after: Option<LogicalTime>,
+    /// `None` means no invocation cap.
+    pub max_invocations: Option<u32>,
+}
+
+impl ScopedConditions {
+    pub fn new(granted_by: OperationId) -> Self {
+        Self {
+            granted_by,
+            not_before: None,
+            not_after: None,
+            max_invocations: None,
+        }
+    }
+
+    pub fn not_before(mut self, at: LogicalTime) -> Self {
+        self.not_before = Some(at);
+        self
+    }
+
+    pub fn not_after(mut self, at: LogicalTime) -> Self {
+        self.not_after = Some(at);
+        self
+    }
+
+    pub fn max_invocations(mut self, max: u32) -> Self {
+        self.max_invocations = Some(max);
+        self
+    }
+
+    fn window_covers(&self, at: LogicalTime) -> bool {
+        self.not_before.is_none_or(|nb| at >= nb) && self.not_after.is_none_or(|na| at < na)
+    }
+
+    fn invocations_remain(&self, invocations_used: u32) -> bool {
+        self.max_invocations.is_none_or(|max| invocations_used < max)
+    }
+}
+
+impl Conditions for ScopedConditions {}
+
+/// Extension of [`Conditions`] for implementations that can lapse. Kept
+/// separate from `Conditions` itself (which stays the empty marker trait
+/// most implementations, like `TestConditions`, are happy with) since
+/// resolving whether a grant still holds needs the operation's logical
+/// time and usage count, which a plain `Conditions` bound doesn't carry.
+pub trait ExpiringConditions: Conditions {
+    /// Whether the grant is still active at `at`, given it has already been
+    /// invoked `invocations_used` times. Nothing in this crate calls it
+    /// when resolving access; `Manager` and `AuthOrderer` are not part of
+    /// this source tree.
+    fn is_active(&self, at: LogicalTime, invocations_used: u32) -> bool;
+}
+
+impl ExpiringConditions for ScopedConditions {
+    fn is_active(&self, at: LogicalTime, invocations_used: u32) -> bool {
+        self.window_covers(at) && self.invocations_remain(invocations_used)
+    }
+}
+
+impl ScopedConditions {
+    /// Resolve whether this grant currently authorizes its holder at `at`,
+    /// reading the invocation count from `invocations` rather than making
+    /// the caller look it up separately. Call
+    /// [`InvocationStore::record_invocation`] afterwards once the operation
+    /// is actually accepted.
+    ///
+    /// Nothing in this crate calls it; `Manager` and `AuthOrderer` are not
+    /// part of this source tree.
+    pub async fn resolve<S: InvocationStore>(
+        &self,
+        at: LogicalTime,
+        invocations: &S,
+    ) -> Result<bool, S::Error> {
+        let invocations_used = invocations.invocations(&self.granted_by).await?;
+        Ok(self.is_active(at, invocations_used))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grant() -> ScopedConditions {
+        ScopedConditions::new(OperationId::new(b"grant"))
+    }
+
+    #[test]
+    fn active_with_no_bounds() {
+        let grant = grant();
+        assert!(grant.is_active(LogicalTime(0), 0));
+        assert!(grant.is_active(LogicalTime(1_000_000), 0));
+    }
+
+    #[test]
+    fn inactive_before_not_before() {
+        let grant = grant().not_before(LogicalTime(10));
+        assert!(!grant.is_active(LogicalTime(9), 0));
+        assert!(grant.is_active(LogicalTime(10), 0));
+    }
+
+    #[test]
+    fn inactive_at_and_after_not_after() {
+        let grant = grant().not_after(LogicalTime(10));
+        assert!(grant.is_active(LogicalTime(9), 0));
+        assert!(!grant.is_active(LogicalTime(10), 0));
+        assert!(!grant.is_active(LogicalTime(11), 0));
+    }
+
+    #[test]
+    fn inactive_once_invocation_cap_reached() {
+        let grant = grant().max_invocations(2);
+        assert!(grant.is_active(LogicalTime(0), 0));
+        assert!(grant.is_active(LogicalTime(0), 1));
+        assert!(!grant.is_active(LogicalTime(0), 2));
+    }
+
+    #[tokio::test]
+    async fn resolve_reads_invocation_count_from_store() {
+        let grant = grant().max_invocations(1);
+        let invocations = MemoryInvocationStore::new();
+
+        assert!(grant.resolve(LogicalTime(0), &invocations).await.unwrap());
+
+        invocations.record_invocation(&grant.granted_by).await.unwrap();
+        assert!(!grant.resolve(LogicalTime(0), &invocations).await.unwrap());
+    }
+
+    #[test]
+    fn regrant_after_expiry_is_distinct_from_lapsed_grant() {
+        let lapsed = ScopedConditions::new(OperationId::new(b"lapsed-grant")).not_after(LogicalTime(10));
+        let regrant = ScopedConditions::new(OperationId::new(b"re-grant")).not_after(LogicalTime(10));
+
+        assert_ne!(lapsed, regrant);
+        assert!(!lapsed.is_active(LogicalTime(10), 0));
+        // The re-grant has its own window and is unaffected by the lapsed
+        // one's expiry.
+        assert!(regrant.is_active(LogicalTime(9), 0));
+    }
+}
@@ -0,0 +1,17 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! `p2panda-spaces`: authenticated, encrypted group spaces on top of
+//! `p2panda-auth`/`p2panda-encryption`.
+//!
+//! This source tree is a snapshot of the crate covering only the
+//! storage, checkpointing, sync and threshold-recovery subsystems; it
+//! does not include `traits`, `types`, `space`, `message`, `event`,
+//! `forge`, `auth` or `manager`, which live elsewhere in the full crate
+//! and are only referenced here by path. Only the modules whose sources
+//! are actually present in this tree are declared below.
+
+pub mod checkpoint;
+pub mod conditions;
+pub mod store;
+pub mod sync;
+pub mod threshold;
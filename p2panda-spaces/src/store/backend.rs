@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Row/blob split underlying the persistent [`SpaceStore`](crate::traits::SpaceStore) family.
+//!
+//! Structured state (`SpaceState`, `AuthGroupState`, key-registry state) is small,
+//! changes shape over time and is always read in full, so it is kept as
+//! [`RowStore`] entries. Operations are large, immutable and append-only once
+//! written, so they are kept as [`BlobStore`] entries instead of being forced
+//! through the same (de)serialization path as rows.
+
+use std::error::Error as StdError;
+
+/// A half-open key range used to page through a [`RowStore`] or [`BlobStore`]
+/// without pulling the whole keyspace into memory.
+///
+/// `start` is inclusive, `end` is exclusive. Either bound may be omitted to
+/// scan to the beginning or end of the keyspace.
+#[derive(Clone, Debug, Default)]
+pub struct KeyRange<K> {
+    pub start: Option<K>,
+    pub end: Option<K>,
+}
+
+impl<K> KeyRange<K> {
+    /// A range covering the entire keyspace.
+    pub fn all() -> Self {
+        Self {
+            start: None,
+            end: None,
+        }
+    }
+
+    /// A range covering every key greater than or equal to `start`.
+    pub fn from(start: K) -> Self {
+        Self {
+            start: Some(start),
+            end: None,
+        }
+    }
+
+    pub fn contains(&self, key: &K) -> bool
+    where
+        K: Ord,
+    {
+        self.start.as_ref().is_none_or(|start| key >= start)
+            && self.end.as_ref().is_none_or(|end| key < end)
+    }
+}
+
+/// Structured, strongly-typed rows addressed by a stable key.
+///
+/// Backs the materialized `SpaceState`, `AuthGroupState` and key-registry
+/// state that `Manager` reads and writes on every operation.
+pub trait RowStore<K, V>: Send + Sync
+where
+    K: Clone + Ord + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    type Error: StdError + Send + Sync + 'static;
+
+    async fn get(&self, key: &K) -> Result<Option<V>, Self::Error>;
+
+    async fn put(&self, key: K, value: V) -> Result<(), Self::Error>;
+
+    async fn rm(&self, key: &K) -> Result<(), Self::Error>;
+
+    async fn list(&self, range: KeyRange<K>) -> Result<Vec<(K, V)>, Self::Error>;
+}
+
+/// Opaque, content-addressed byte blobs.
+///
+/// Backs the append-only operation log. Blobs are never interpreted by the
+/// store itself, only copied in and out, so they can be handed off to a
+/// plain object store rather than a structured database.
+pub trait BlobStore<K>: Send + Sync
+where
+    K: Clone + Ord + Send + Sync,
+{
+    type Error: StdError + Send + Sync + 'static;
+
+    async fn get(&self, key: &K) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    async fn put(&self, key: K, bytes: Vec<u8>) -> Result<(), Self::Error>;
+
+    async fn rm(&self, key: &K) -> Result<(), Self::Error>;
+
+    async fn list(&self, range: KeyRange<K>) -> Result<Vec<K>, Self::Error>;
+}
@@ -0,0 +1,130 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::store::backend::{BlobStore, KeyRange, RowStore};
+
+/// In-memory [`RowStore`], backing [`MemoryStore`](crate::test_utils::MemoryStore)
+/// and useful as a `RowStore`/`BlobStore` reference implementation for tests.
+#[derive(Debug, Clone)]
+pub struct MemoryRowStore<K, V> {
+    rows: Arc<RwLock<BTreeMap<K, V>>>,
+}
+
+impl<K, V> MemoryRowStore<K, V>
+where
+    K: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            rows: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+}
+
+impl<K, V> Default for MemoryRowStore<K, V>
+where
+    K: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> RowStore<K, V> for MemoryRowStore<K, V>
+where
+    K: Clone + Ord + Send + Sync,
+    V: Clone + Send + Sync,
+{
+    type Error = Infallible;
+
+    async fn get(&self, key: &K) -> Result<Option<V>, Self::Error> {
+        let rows = self.rows.read().await;
+        Ok(rows.get(key).cloned())
+    }
+
+    async fn put(&self, key: K, value: V) -> Result<(), Self::Error> {
+        let mut rows = self.rows.write().await;
+        rows.insert(key, value);
+        Ok(())
+    }
+
+    async fn rm(&self, key: &K) -> Result<(), Self::Error> {
+        let mut rows = self.rows.write().await;
+        rows.remove(key);
+        Ok(())
+    }
+
+    async fn list(&self, range: KeyRange<K>) -> Result<Vec<(K, V)>, Self::Error> {
+        let rows = self.rows.read().await;
+        Ok(rows
+            .iter()
+            .filter(|(key, _)| range.contains(key))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+    }
+}
+
+/// In-memory [`BlobStore`], backing [`MemoryStore`](crate::test_utils::MemoryStore)
+/// and useful as a `RowStore`/`BlobStore` reference implementation for tests.
+#[derive(Debug, Clone)]
+pub struct MemoryBlobStore<K> {
+    blobs: Arc<RwLock<BTreeMap<K, Vec<u8>>>>,
+}
+
+impl<K> MemoryBlobStore<K>
+where
+    K: Ord,
+{
+    pub fn new() -> Self {
+        Self {
+            blobs: Arc::new(RwLock::new(BTreeMap::new())),
+        }
+    }
+}
+
+impl<K> Default for MemoryBlobStore<K>
+where
+    K: Ord,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K> BlobStore<K> for MemoryBlobStore<K>
+where
+    K: Clone + Ord + Send + Sync,
+{
+    type Error = Infallible;
+
+    async fn get(&self, key: &K) -> Result<Option<Vec<u8>>, Self::Error> {
+        let blobs = self.blobs.read().await;
+        Ok(blobs.get(key).cloned())
+    }
+
+    async fn put(&self, key: K, bytes: Vec<u8>) -> Result<(), Self::Error> {
+        let mut blobs = self.blobs.write().await;
+        blobs.insert(key, bytes);
+        Ok(())
+    }
+
+    async fn rm(&self, key: &K) -> Result<(), Self::Error> {
+        let mut blobs = self.blobs.write().await;
+        blobs.remove(key);
+        Ok(())
+    }
+
+    async fn list(&self, range: KeyRange<K>) -> Result<Vec<K>, Self::Error> {
+        let blobs = self.blobs.read().await;
+        Ok(blobs
+            .keys()
+            .filter(|key| range.contains(key))
+            .cloned()
+            .collect())
+    }
+}
@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Row/blob storage primitives for persistent [`SpaceStore`](crate::traits::SpaceStore)
+//! implementations.
+//!
+//! [`MemoryStore`](crate::test_utils::MemoryStore) is built on the in-memory
+//! backends here and remains the default for tests. For a deployment that
+//! needs to survive a restart, combine a [`RowStore`]/[`BlobStore`] pair with
+//! [`PersistentStore`] instead of hand-rolling the five store traits again.
+
+mod backend;
+mod memory;
+mod persistent;
+
+#[cfg(feature = "object-store")]
+mod object_store;
+#[cfg(feature = "sled")]
+mod sled;
+
+pub use backend::{BlobStore, KeyRange, RowStore};
+pub use memory::{MemoryBlobStore, MemoryRowStore};
+pub use persistent::{PersistentMessageStoreError, PersistentStore};
+// Re-exported so existing call sites that reach the store traits through
+// `crate::store` (rather than `crate::traits` directly) keep compiling.
+pub use crate::traits::{AuthStore, SpaceStore};
+
+#[cfg(feature = "object-store")]
+pub use object_store::{ObjectBlobStore, ObjectBlobStoreError, ObjectKey};
+#[cfg(feature = "sled")]
+pub use sled::{SledBlobStore, SledRowStore, SledStoreError};
@@ -0,0 +1,151 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! S3-compatible [`BlobStore`] built on the `object_store` crate.
+//!
+//! Operations are immutable once written and never queried by content, so
+//! they map cleanly onto object storage keys. We do not offer an
+//! object-store-backed [`RowStore`]: structured state is read and rewritten
+//! on every applied operation, and paying a network round trip per row
+//! read would make `Manager` unusable, so rows stay on [`SledRowStore`](crate::store::sled::SledRowStore)
+//! or [`MemoryRowStore`](crate::store::memory::MemoryRowStore).
+
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use object_store::{ObjectStore, path::Path};
+
+use crate::OperationId;
+use crate::store::backend::{BlobStore, KeyRange};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ObjectBlobStoreError {
+    #[error(transparent)]
+    ObjectStore(#[from] object_store::Error),
+
+    #[error("key does not form a valid object store path: {0}")]
+    InvalidKey(String),
+}
+
+/// Maps a row/blob key to an `object_store::path::Path`, so callers can key
+/// the blob tree by `OperationId` (or any other `Display`-able key) while
+/// controlling the object prefix under which it is stored.
+pub trait ObjectKey: Send + Sync {
+    fn to_path(&self, prefix: &str) -> Path;
+
+    fn from_path(path: &Path, prefix: &str) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+/// So `PersistentStore`'s `B: BlobStore<OperationId>` bound can actually be
+/// satisfied by an `ObjectBlobStore`, the way the request's "one
+/// object-store implementation" asks for. `OperationId`'s `Display` is its
+/// hex encoding (see `p2panda_core::Hash`), which round-trips cleanly
+/// through an object store path segment.
+impl ObjectKey for OperationId {
+    fn to_path(&self, prefix: &str) -> Path {
+        Path::from(format!("{prefix}/{self}"))
+    }
+
+    fn from_path(path: &Path, prefix: &str) -> Option<Self> {
+        let rest = path.as_ref().strip_prefix(prefix)?;
+        rest.trim_start_matches('/').parse().ok()
+    }
+}
+
+#[derive(Clone)]
+pub struct ObjectBlobStore<K> {
+    store: Arc<dyn ObjectStore>,
+    prefix: String,
+    _phantom: PhantomData<K>,
+}
+
+impl<K> ObjectBlobStore<K> {
+    pub fn new(store: Arc<dyn ObjectStore>, prefix: impl Into<String>) -> Self {
+        Self {
+            store,
+            prefix: prefix.into(),
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<K> BlobStore<K> for ObjectBlobStore<K>
+where
+    K: Clone + Ord + ObjectKey,
+{
+    type Error = ObjectBlobStoreError;
+
+    async fn get(&self, key: &K) -> Result<Option<Vec<u8>>, Self::Error> {
+        let path = key.to_path(&self.prefix);
+        match self.store.get(&path).await {
+            Ok(result) => Ok(Some(result.bytes().await?.to_vec())),
+            Err(object_store::Error::NotFound { .. }) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn put(&self, key: K, bytes: Vec<u8>) -> Result<(), Self::Error> {
+        let path = key.to_path(&self.prefix);
+        self.store.put(&path, Bytes::from(bytes).into()).await?;
+        Ok(())
+    }
+
+    async fn rm(&self, key: &K) -> Result<(), Self::Error> {
+        let path = key.to_path(&self.prefix);
+        match self.store.delete(&path).await {
+            Ok(()) | Err(object_store::Error::NotFound { .. }) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn list(&self, range: KeyRange<K>) -> Result<Vec<K>, Self::Error> {
+        use futures_util::StreamExt;
+
+        let prefix_path = Path::from(self.prefix.as_str());
+        let mut stream = self.store.list(Some(&prefix_path));
+        let mut out = Vec::new();
+        while let Some(meta) = stream.next().await {
+            let meta = meta?;
+            let Some(key) = K::from_path(&meta.location, &self.prefix) else {
+                continue;
+            };
+            if range.contains(&key) {
+                out.push(key);
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use object_store::memory::InMemory;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn round_trips_operation_ids_through_object_storage() {
+        let store: ObjectBlobStore<OperationId> =
+            ObjectBlobStore::new(Arc::new(InMemory::new()), "operations");
+
+        let a = OperationId::new(b"operation a");
+        let b = OperationId::new(b"operation b");
+
+        store.put(a.clone(), b"payload a".to_vec()).await.unwrap();
+        store.put(b.clone(), b"payload b".to_vec()).await.unwrap();
+
+        assert_eq!(store.get(&a).await.unwrap(), Some(b"payload a".to_vec()));
+        assert_eq!(store.get(&b).await.unwrap(), Some(b"payload b".to_vec()));
+
+        let mut listed = store.list(KeyRange::all()).await.unwrap();
+        listed.sort();
+        let mut expected = vec![a.clone(), b];
+        expected.sort();
+        assert_eq!(listed, expected);
+
+        store.rm(&a).await.unwrap();
+        assert_eq!(store.get(&a).await.unwrap(), None);
+    }
+}
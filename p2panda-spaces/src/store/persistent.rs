@@ -0,0 +1,506 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Production [`SpaceStore`]/[`AuthStore`]/[`MessageStore`]/[`KeyRegistryStore`]/
+//! [`KeySecretStore`] implementation generic over a [`RowStore`] and a [`BlobStore`].
+//!
+//! This is the persistent counterpart to [`MemoryStore`](crate::test_utils::MemoryStore)
+//! and `TestKeyStore`: it implements the five store traits those two
+//! implement between them, but takes no locks of its own and sends every
+//! read and write through `R`/`B` instead of in-process maps, so state
+//! survives a restart. Pick `R`/`B` to match your deployment, e.g.
+//! [`SledRowStore`](crate::store::sled::SledRowStore) + [`SledBlobStore`](crate::store::sled::SledBlobStore)
+//! for a single-node deployment, or a row store of your choice paired with
+//! [`ObjectBlobStore`](crate::store::object_store::ObjectBlobStore) when operations
+//! should live in S3-compatible storage.
+
+use std::hash::Hash as StdHash;
+
+use p2panda_auth::traits::Conditions;
+use p2panda_encryption::key_manager::PreKeyBundlesState;
+use p2panda_encryption::key_registry::KeyRegistryState;
+
+use crate::OperationId;
+use crate::store::backend::{BlobStore, KeyRange, RowStore};
+use crate::traits::{AuthStore, KeyRegistryStore, KeySecretStore, MessageStore, SpaceId, SpaceStore};
+use crate::types::{ActorId, AuthGroupState};
+use crate::space::SpaceState;
+
+/// The fixed row keys used to address the singleton rows (auth state, key
+/// registry, prekey secrets) that every `PersistentStore` keeps alongside
+/// per-space rows.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+enum SingletonKey {
+    Auth,
+    KeyRegistry,
+    PrekeySecrets,
+}
+
+/// Row store key for a `PersistentStore`: either a per-space row or one of
+/// the fixed singleton rows.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+enum RowKey<I> {
+    Space(I),
+    Singleton(SingletonKey),
+}
+
+pub struct PersistentStore<I, M, C, R, B>
+where
+    C: Conditions,
+{
+    rows: R,
+    blobs: B,
+    _phantom: std::marker::PhantomData<(I, M, C)>,
+}
+
+impl<I, M, C, R, B> PersistentStore<I, M, C, R, B>
+where
+    C: Conditions,
+{
+    pub fn new(rows: R, blobs: B) -> Self {
+        Self {
+            rows,
+            blobs,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    async fn singleton<V>(&self, key: SingletonKey) -> Result<Option<V>, R::Error>
+    where
+        R: RowStore<RowKey<I>, PersistentRow<I, M, C>>,
+        I: Clone + Ord + Send + Sync,
+        V: TryFrom<PersistentRow<I, M, C>>,
+    {
+        // `TryFrom` never fails in practice since each singleton key only
+        // ever stores one `PersistentRow` variant; kept as a `Result` to
+        // avoid panicking on a corrupted store.
+        Ok(self
+            .rows
+            .get(&RowKey::Singleton(key))
+            .await?
+            .and_then(|row| V::try_from(row).ok()))
+    }
+}
+
+/// Row payload stored under a [`RowKey`]. A single enum keeps `PersistentStore`
+/// to one `RowStore` instance rather than one per kind of state, mirroring
+/// how [`MemoryStore`](crate::test_utils::MemoryStore) keeps everything behind
+/// one lock.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+#[serde(bound = "")]
+enum PersistentRow<I, M, C>
+where
+    C: Conditions,
+{
+    Space(SpaceState<I, M, C>),
+    Auth(AuthGroupState<C>),
+    KeyRegistry(KeyRegistryState<ActorId>),
+    PrekeySecrets(PreKeyBundlesState),
+}
+
+macro_rules! try_from_row {
+    ($variant:ident, $ty:ty) => {
+        impl<I, M, C> TryFrom<PersistentRow<I, M, C>> for $ty
+        where
+            C: Conditions,
+        {
+            type Error = ();
+
+            fn try_from(row: PersistentRow<I, M, C>) -> Result<Self, Self::Error> {
+                match row {
+                    PersistentRow::$variant(inner) => Ok(inner),
+                    _ => Err(()),
+                }
+            }
+        }
+    };
+}
+
+try_from_row!(Auth, AuthGroupState<C>);
+try_from_row!(KeyRegistry, KeyRegistryState<ActorId>);
+try_from_row!(PrekeySecrets, PreKeyBundlesState);
+
+impl<I, M, C, R, B> SpaceStore<I, M, C> for PersistentStore<I, M, C, R, B>
+where
+    I: SpaceId + StdHash + Eq + Send + Sync,
+    M: Clone + Send + Sync,
+    C: Conditions + Send + Sync,
+    R: RowStore<RowKey<I>, PersistentRow<I, M, C>>,
+    B: BlobStore<OperationId> + Send + Sync,
+{
+    type Error = R::Error;
+
+    async fn space(&self, id: &I) -> Result<Option<SpaceState<I, M, C>>, Self::Error> {
+        Ok(self
+            .rows
+            .get(&RowKey::Space(*id))
+            .await?
+            .and_then(|row| match row {
+                PersistentRow::Space(state) => Some(state),
+                _ => None,
+            }))
+    }
+
+    async fn has_space(&self, id: &I) -> Result<bool, Self::Error> {
+        Ok(self.space(id).await?.is_some())
+    }
+
+    async fn spaces_ids(&self) -> Result<Vec<I>, Self::Error> {
+        Ok(self
+            .rows
+            .list(KeyRange::all())
+            .await?
+            .into_iter()
+            .filter_map(|(key, _)| match key {
+                RowKey::Space(id) => Some(id),
+                RowKey::Singleton(_) => None,
+            })
+            .collect())
+    }
+
+    async fn set_space(&self, id: &I, y: SpaceState<I, M, C>) -> Result<(), Self::Error> {
+        self.rows
+            .put(RowKey::Space(*id), PersistentRow::Space(y))
+            .await
+    }
+}
+
+impl<I, M, C, R, B> AuthStore<C> for PersistentStore<I, M, C, R, B>
+where
+    I: SpaceId + StdHash + Eq + Send + Sync,
+    C: Conditions + Send + Sync,
+    R: RowStore<RowKey<I>, PersistentRow<I, M, C>>,
+    B: BlobStore<OperationId> + Send + Sync,
+{
+    type Error = R::Error;
+
+    async fn auth(&self) -> Result<AuthGroupState<C>, Self::Error> {
+        Ok(self
+            .singleton(SingletonKey::Auth)
+            .await?
+            .unwrap_or_else(|| AuthGroupState::new(crate::auth::orderer::AuthOrderer::init())))
+    }
+
+    async fn set_auth(&self, y: &AuthGroupState<C>) -> Result<(), Self::Error> {
+        self.rows
+            .put(
+                RowKey::Singleton(SingletonKey::Auth),
+                PersistentRow::Auth(y.clone()),
+            )
+            .await
+    }
+}
+
+impl<I, M, C, R, B> MessageStore<M> for PersistentStore<I, M, C, R, B>
+where
+    I: SpaceId + StdHash + Eq + Send + Sync,
+    M: Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned,
+    C: Conditions + Send + Sync,
+    R: RowStore<RowKey<I>, PersistentRow<I, M, C>>,
+    B: BlobStore<OperationId> + Send + Sync,
+{
+    type Error = PersistentMessageStoreError<R::Error, B::Error>;
+
+    async fn message(&self, id: &OperationId) -> Result<Option<M>, Self::Error> {
+        let Some(bytes) = self
+            .blobs
+            .get(id)
+            .await
+            .map_err(PersistentMessageStoreError::Blob)?
+        else {
+            return Ok(None);
+        };
+        Ok(Some(
+            ciborium::from_reader(bytes.as_slice())
+                .map_err(PersistentMessageStoreError::Decode)?,
+        ))
+    }
+
+    async fn set_message(&self, id: &OperationId, message: &M) -> Result<(), Self::Error> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(message, &mut buf).map_err(PersistentMessageStoreError::Encode)?;
+        self.blobs
+            .put(*id, buf)
+            .await
+            .map_err(PersistentMessageStoreError::Blob)
+    }
+}
+
+impl<I, M, C, R, B> crate::sync::SelectableMessageStore<M> for PersistentStore<I, M, C, R, B>
+where
+    I: SpaceId + StdHash + Eq + Send + Sync,
+    M: Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned + crate::sync::SequencedMessage,
+    C: Conditions + Send + Sync,
+    R: RowStore<RowKey<I>, PersistentRow<I, M, C>>,
+    B: BlobStore<OperationId> + Send + Sync,
+{
+    async fn select(&self, selector: crate::sync::Selector) -> Result<Vec<M>, Self::Error> {
+        let ids = self
+            .blobs
+            .list(KeyRange::all())
+            .await
+            .map_err(PersistentMessageStoreError::Blob)?;
+        let mut matched = Vec::new();
+        for id in ids {
+            if let Some(message) = self.message(&id).await? {
+                if selector.matches(&message) {
+                    matched.push(message);
+                }
+            }
+        }
+        matched.sort_by_key(crate::sync::SequencedMessage::seq_num);
+        Ok(matched)
+    }
+
+    fn subscribe(&self) -> tokio::sync::broadcast::Receiver<OperationId> {
+        // A persistent backend is typically shared across processes, so an
+        // in-process broadcast channel can't see every writer. The returned
+        // receiver's sender is dropped at once, so it never yields a
+        // notification: writes through `set_message` are not broadcast.
+        // Real deployments should watch the underlying row/blob store's own
+        // change feed instead; this is here only so `PersistentStore`
+        // type-checks against `SyncStore`.
+        tokio::sync::broadcast::channel(1).1
+    }
+}
+
+impl<I, M, C, R, B> crate::checkpoint::PrunableMessageStore<M> for PersistentStore<I, M, C, R, B>
+where
+    I: SpaceId + StdHash + Eq + Send + Sync,
+    M: Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned,
+    C: Conditions + Send + Sync,
+    R: RowStore<RowKey<I>, PersistentRow<I, M, C>>,
+    B: BlobStore<OperationId> + Send + Sync,
+{
+    async fn remove_message(&self, id: &OperationId) -> Result<(), Self::Error> {
+        self.blobs.rm(id).await.map_err(PersistentMessageStoreError::Blob)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PersistentMessageStoreError<R, B> {
+    #[error("row store error: {0}")]
+    Row(R),
+
+    #[error("blob store error: {0}")]
+    Blob(B),
+
+    #[error("failed to encode message: {0}")]
+    Encode(ciborium::ser::Error<std::io::Error>),
+
+    #[error("failed to decode message: {0}")]
+    Decode(ciborium::de::Error<std::io::Error>),
+}
+
+impl<I, M, C, R, B> KeyRegistryStore for PersistentStore<I, M, C, R, B>
+where
+    I: Clone + Ord + Send + Sync,
+    M: Send + Sync,
+    C: Conditions + Send + Sync,
+    R: RowStore<RowKey<I>, PersistentRow<I, M, C>>,
+    B: Send + Sync,
+{
+    type Error = R::Error;
+
+    async fn key_registry(&self) -> Result<KeyRegistryState<ActorId>, Self::Error> {
+        Ok(self
+            .singleton(SingletonKey::KeyRegistry)
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn set_key_registry(&self, y: &KeyRegistryState<ActorId>) -> Result<(), Self::Error> {
+        self.rows
+            .put(
+                RowKey::Singleton(SingletonKey::KeyRegistry),
+                PersistentRow::KeyRegistry(y.clone()),
+            )
+            .await
+    }
+}
+
+impl<I, M, C, R, B> KeySecretStore for PersistentStore<I, M, C, R, B>
+where
+    I: Clone + Ord + Send + Sync,
+    M: Send + Sync,
+    C: Conditions + Send + Sync,
+    R: RowStore<RowKey<I>, PersistentRow<I, M, C>>,
+    B: Send + Sync,
+{
+    type Error = R::Error;
+
+    async fn prekey_secrets(&self) -> Result<PreKeyBundlesState, Self::Error> {
+        Ok(self
+            .singleton(SingletonKey::PrekeySecrets)
+            .await?
+            .unwrap_or_default())
+    }
+
+    async fn set_prekey_secrets(&self, y: &PreKeyBundlesState) -> Result<(), Self::Error> {
+        self.rows
+            .put(
+                RowKey::Singleton(SingletonKey::PrekeySecrets),
+                PersistentRow::PrekeySecrets(y.clone()),
+            )
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::AuthoredMessage;
+    use crate::store::memory::{MemoryBlobStore, MemoryRowStore};
+    use crate::sync::{SelectableMessageStore, Selector, SequencedMessage};
+    use crate::traits::SpaceId;
+    use crate::types::ActorId;
+
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, serde::Serialize, serde::Deserialize)]
+    struct MiniSpaceId(u64);
+
+    impl SpaceId for MiniSpaceId {}
+
+    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct MiniConditions;
+
+    impl Conditions for MiniConditions {}
+
+    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct MiniMessage {
+        author: ActorId,
+        seq_num: u64,
+    }
+
+    impl AuthoredMessage for MiniMessage {
+        fn id(&self) -> OperationId {
+            OperationId::new(format!("{}:{}", self.author, self.seq_num).as_bytes())
+        }
+
+        fn author(&self) -> ActorId {
+            self.author
+        }
+    }
+
+    impl SequencedMessage for MiniMessage {
+        fn seq_num(&self) -> u64 {
+            self.seq_num
+        }
+    }
+
+    type TestStore = PersistentStore<
+        MiniSpaceId,
+        MiniMessage,
+        MiniConditions,
+        MemoryRowStore<RowKey<MiniSpaceId>, PersistentRow<MiniSpaceId, MiniMessage, MiniConditions>>,
+        MemoryBlobStore<OperationId>,
+    >;
+
+    fn store() -> TestStore {
+        PersistentStore::new(MemoryRowStore::new(), MemoryBlobStore::new())
+    }
+
+    fn actor(seed: u8) -> ActorId {
+        p2panda_core::PrivateKey::from_bytes(&[seed; 32]).public_key().into()
+    }
+
+    // `PersistentStore::space`/`set_space` round-trip SpaceState, which
+    // nothing in this tree can construct (no `space.rs`, no known
+    // constructor anywhere in the diff), so it is untested here; the rest of
+    // `PersistentStore`'s responsibilities - message blobs, sync selection,
+    // and the singleton rows that don't depend on `SpaceState` - are.
+
+    #[tokio::test]
+    async fn message_round_trips_through_the_blob_store() {
+        let store = store();
+        let message = MiniMessage {
+            author: actor(1),
+            seq_num: 0,
+        };
+        let id = message.id();
+
+        assert_eq!(store.message(&id).await.unwrap(), None);
+        store.set_message(&id, &message).await.unwrap();
+        assert_eq!(store.message(&id).await.unwrap().unwrap().seq_num, 0);
+    }
+
+    #[tokio::test]
+    async fn select_sees_stored_messages() {
+        let store = store();
+        let author = actor(2);
+        let other_author = actor(3);
+
+        for seq_num in 0..3 {
+            let message = MiniMessage { author, seq_num };
+            store.set_message(&message.id(), &message).await.unwrap();
+        }
+        let other = MiniMessage {
+            author: other_author,
+            seq_num: 0,
+        };
+        store.set_message(&other.id(), &other).await.unwrap();
+
+        let selected = store
+            .select(Selector::Prefix { author })
+            .await
+            .unwrap();
+        assert_eq!(
+            selected.iter().map(|m| m.seq_num).collect::<Vec<_>>(),
+            vec![0, 1, 2]
+        );
+    }
+
+    #[tokio::test]
+    async fn auth_state_round_trips_through_the_row_store() {
+        let store = store();
+
+        let state = AuthGroupState::new(crate::auth::orderer::AuthOrderer::init());
+        store.set_auth(&state).await.unwrap();
+
+        // `AuthGroupState` isn't known to implement `PartialEq` in this
+        // tree, so this only checks that `set_auth` stores an auth row that
+        // reads back as an `AuthGroupState` without error - not that its
+        // contents are identical to what was written.
+        assert!(store.auth().await.is_ok());
+        assert!(
+            store
+                .singleton::<AuthGroupState<MiniConditions>>(SingletonKey::Auth)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn key_registry_and_prekey_secrets_round_trip_as_singleton_rows() {
+        let store = store();
+
+        assert_eq!(store.key_registry().await.unwrap(), KeyRegistryState::default());
+        store
+            .set_key_registry(&KeyRegistryState::default())
+            .await
+            .unwrap();
+        assert_eq!(store.key_registry().await.unwrap(), KeyRegistryState::default());
+
+        assert_eq!(store.prekey_secrets().await.unwrap(), PreKeyBundlesState::default());
+        store
+            .set_prekey_secrets(&PreKeyBundlesState::default())
+            .await
+            .unwrap();
+        assert_eq!(store.prekey_secrets().await.unwrap(), PreKeyBundlesState::default());
+    }
+
+    #[tokio::test]
+    async fn spaces_ids_is_empty_when_no_space_rows_are_set() {
+        // No `SpaceState` can be constructed in this tree to put a space row
+        // in the first place, but `spaces_ids` should still report an empty
+        // list rather than erroring against a store that has only singleton
+        // rows.
+        let store = store();
+        store
+            .set_auth(&AuthGroupState::new(crate::auth::orderer::AuthOrderer::init()))
+            .await
+            .unwrap();
+
+        assert_eq!(store.spaces_ids().await.unwrap(), Vec::<MiniSpaceId>::new());
+    }
+}
@@ -0,0 +1,147 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Filesystem-backed [`RowStore`]/[`BlobStore`] pair built on `sled`.
+//!
+//! Rows are CBOR-encoded before being written to their own `sled::Tree` so
+//! the on-disk format matches what peers exchange over the wire. Blobs are
+//! stored verbatim, since they already arrive as encoded operation bytes.
+
+use std::marker::PhantomData;
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::store::backend::{BlobStore, KeyRange, RowStore};
+
+#[derive(Debug, thiserror::Error)]
+pub enum SledStoreError {
+    #[error(transparent)]
+    Sled(#[from] sled::Error),
+
+    #[error("failed to encode row: {0}")]
+    Encode(#[from] ciborium::ser::Error<std::io::Error>),
+
+    #[error("failed to decode row: {0}")]
+    Decode(#[from] ciborium::de::Error<std::io::Error>),
+
+    #[error("key is not valid CBOR: {0}")]
+    KeyEncode(ciborium::ser::Error<std::io::Error>),
+}
+
+/// Rows live in their own `sled::Tree`, keyed by the CBOR encoding of `K` so
+/// key ordering on disk matches `K`'s `Ord` impl as long as `K` encodes to a
+/// fixed-width representation (true for every `SpaceId`/`ActorId` in this
+/// crate).
+#[derive(Debug, Clone)]
+pub struct SledRowStore<K, V> {
+    tree: sled::Tree,
+    _phantom: PhantomData<(K, V)>,
+}
+
+impl<K, V> SledRowStore<K, V> {
+    pub fn new(db: &sled::Db, tree_name: &str) -> Result<Self, SledStoreError> {
+        Ok(Self {
+            tree: db.open_tree(tree_name)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+fn encode_key<K: Serialize>(key: &K) -> Result<Vec<u8>, SledStoreError> {
+    let mut buf = Vec::new();
+    ciborium::into_writer(key, &mut buf).map_err(SledStoreError::KeyEncode)?;
+    Ok(buf)
+}
+
+impl<K, V> RowStore<K, V> for SledRowStore<K, V>
+where
+    K: Clone + Ord + Serialize + DeserializeOwned + Send + Sync,
+    V: Clone + Serialize + DeserializeOwned + Send + Sync,
+{
+    type Error = SledStoreError;
+
+    async fn get(&self, key: &K) -> Result<Option<V>, Self::Error> {
+        let Some(bytes) = self.tree.get(encode_key(key)?)? else {
+            return Ok(None);
+        };
+        Ok(Some(ciborium::from_reader(bytes.as_ref())?))
+    }
+
+    async fn put(&self, key: K, value: V) -> Result<(), Self::Error> {
+        let mut buf = Vec::new();
+        ciborium::into_writer(&value, &mut buf)?;
+        self.tree.insert(encode_key(&key)?, buf)?;
+        Ok(())
+    }
+
+    async fn rm(&self, key: &K) -> Result<(), Self::Error> {
+        self.tree.remove(encode_key(key)?)?;
+        Ok(())
+    }
+
+    async fn list(&self, range: KeyRange<K>) -> Result<Vec<(K, V)>, Self::Error> {
+        let mut out = Vec::new();
+        for entry in self.tree.iter() {
+            let (key_bytes, value_bytes) = entry?;
+            let key: K = ciborium::from_reader(key_bytes.as_ref())?;
+            if !range.contains(&key) {
+                continue;
+            }
+            out.push((key, ciborium::from_reader(value_bytes.as_ref())?));
+        }
+        Ok(out)
+    }
+}
+
+/// Blobs are stored verbatim in their own `sled::Tree`; no (de)serialization
+/// happens on this side of the split.
+#[derive(Debug, Clone)]
+pub struct SledBlobStore<K> {
+    tree: sled::Tree,
+    _phantom: PhantomData<K>,
+}
+
+impl<K> SledBlobStore<K> {
+    pub fn new(db: &sled::Db, tree_name: &str) -> Result<Self, SledStoreError> {
+        Ok(Self {
+            tree: db.open_tree(tree_name)?,
+            _phantom: PhantomData,
+        })
+    }
+}
+
+impl<K> BlobStore<K> for SledBlobStore<K>
+where
+    K: Clone + Ord + Serialize + DeserializeOwned + Send + Sync,
+{
+    type Error = SledStoreError;
+
+    async fn get(&self, key: &K) -> Result<Option<Vec<u8>>, Self::Error> {
+        Ok(self
+            .tree
+            .get(encode_key(key)?)?
+            .map(|bytes| bytes.to_vec()))
+    }
+
+    async fn put(&self, key: K, bytes: Vec<u8>) -> Result<(), Self::Error> {
+        self.tree.insert(encode_key(&key)?, bytes)?;
+        Ok(())
+    }
+
+    async fn rm(&self, key: &K) -> Result<(), Self::Error> {
+        self.tree.remove(encode_key(key)?)?;
+        Ok(())
+    }
+
+    async fn list(&self, range: KeyRange<K>) -> Result<Vec<K>, Self::Error> {
+        let mut out = Vec::new();
+        for entry in self.tree.iter() {
+            let (key_bytes, _) = entry?;
+            let key: K = ciborium::from_reader(key_bytes.as_ref())?;
+            if range.contains(&key) {
+                out.push(key);
+            }
+        }
+        Ok(out)
+    }
+}
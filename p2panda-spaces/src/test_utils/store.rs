@@ -1,6 +1,5 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use std::collections::HashMap;
 use std::convert::Infallible;
 use std::sync::Arc;
 
@@ -8,10 +7,14 @@ use p2panda_auth::traits::Conditions;
 use p2panda_encryption::key_manager::PreKeyBundlesState;
 use p2panda_encryption::key_registry::{KeyRegistry, KeyRegistryState};
 use tokio::sync::RwLock;
+use tokio::sync::broadcast;
 
 use crate::OperationId;
 use crate::auth::orderer::AuthOrderer;
+use crate::checkpoint::PrunableMessageStore;
 use crate::space::SpaceState;
+use crate::store::{BlobStore, KeyRange, MemoryBlobStore, MemoryRowStore, RowStore};
+use crate::sync::{SelectableMessageStore, SequencedMessage, Selector};
 use crate::test_utils::{TestConditions, TestMessage, TestSpaceId};
 use crate::traits::{
     AuthStore, KeyRegistryStore, KeySecretStore, MessageStore, SpaceId, SpaceStore,
@@ -20,38 +23,39 @@ use crate::types::{ActorId, AuthGroupState};
 
 pub type TestStore = MemoryStore<TestSpaceId, TestMessage, TestConditions>;
 
-#[derive(Debug)]
-pub struct MemoryStoreInner<I, M, C>
-where
-    C: Conditions,
-{
-    auth: AuthGroupState<C>,
-    spaces: HashMap<I, SpaceState<I, M, C>>,
-    messages: HashMap<OperationId, M>,
-}
+/// Number of lagging-subscriber notifications the `notify` channel buffers
+/// before dropping the oldest one. Subscribers that fall behind this far are
+/// expected to catch up with `select`, not rely on every notification.
+const NOTIFY_CAPACITY: usize = 256;
 
+/// Reference [`SpaceStore`]/[`AuthStore`]/[`MessageStore`] impl used throughout the
+/// test suite. Spaces live in a [`MemoryRowStore`] and operations in a
+/// [`MemoryBlobStore`], mirroring the split a persistent backend
+/// (`crate::store::PersistentStore`) makes for real; the auth group is kept
+/// behind its own lock.
 #[derive(Debug, Clone)]
 pub struct MemoryStore<I, M, C>
 where
     C: Conditions,
 {
-    pub(crate) inner: Arc<RwLock<MemoryStoreInner<I, M, C>>>,
+    auth: Arc<RwLock<AuthGroupState<C>>>,
+    spaces: MemoryRowStore<I, SpaceState<I, M, C>>,
+    messages: MemoryBlobStore<OperationId>,
+    notify: broadcast::Sender<OperationId>,
 }
 
 impl<I, M, C> MemoryStore<I, M, C>
 where
+    I: Ord,
     C: Conditions,
 {
     pub fn new() -> Self {
         let orderer_y = AuthOrderer::init();
-        let auth_y = AuthGroupState::new(orderer_y);
-        let inner = MemoryStoreInner {
-            auth: auth_y,
-            spaces: HashMap::new(),
-            messages: HashMap::new(),
-        };
         Self {
-            inner: Arc::new(RwLock::new(inner)),
+            auth: Arc::new(RwLock::new(AuthGroupState::new(orderer_y))),
+            spaces: MemoryRowStore::new(),
+            messages: MemoryBlobStore::new(),
+            notify: broadcast::channel(NOTIFY_CAPACITY).0,
         }
     }
 }
@@ -59,30 +63,31 @@ where
 impl<I, M, C> SpaceStore<I, M, C> for MemoryStore<I, M, C>
 where
     I: SpaceId + std::hash::Hash + Eq,
-    M: Clone,
-    C: Conditions,
+    M: Clone + Send + Sync,
+    C: Conditions + Send + Sync,
 {
     type Error = Infallible;
 
     async fn space(&self, id: &I) -> Result<Option<SpaceState<I, M, C>>, Self::Error> {
-        let inner = self.inner.read().await;
-        Ok(inner.spaces.get(id).cloned())
+        self.spaces.get(id).await
     }
 
     async fn has_space(&self, id: &I) -> Result<bool, Self::Error> {
-        let inner = self.inner.read().await;
-        Ok(inner.spaces.contains_key(id))
+        Ok(self.spaces.get(id).await?.is_some())
     }
 
     async fn spaces_ids(&self) -> Result<Vec<I>, Self::Error> {
-        let inner = self.inner.read().await;
-        Ok(inner.spaces.keys().cloned().collect())
+        Ok(self
+            .spaces
+            .list(KeyRange::all())
+            .await?
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect())
     }
 
     async fn set_space(&self, id: &I, y: SpaceState<I, M, C>) -> Result<(), Self::Error> {
-        let mut inner = self.inner.write().await;
-        inner.spaces.insert(*id, y);
-        Ok(())
+        self.spaces.put(*id, y).await
     }
 }
 
@@ -94,13 +99,13 @@ where
     type Error = Infallible;
 
     async fn auth(&self) -> Result<AuthGroupState<C>, Self::Error> {
-        let inner = self.inner.read().await;
-        Ok(inner.auth.clone())
+        let auth = self.auth.read().await;
+        Ok(auth.clone())
     }
 
     async fn set_auth(&self, y: &AuthGroupState<C>) -> Result<(), Self::Error> {
-        let mut inner = self.inner.write().await;
-        inner.auth = y.clone();
+        let mut auth = self.auth.write().await;
+        *auth = y.clone();
         Ok(())
     }
 }
@@ -108,23 +113,68 @@ where
 impl<I, M, C> MessageStore<M> for MemoryStore<I, M, C>
 where
     I: SpaceId + std::hash::Hash + Eq,
-    M: Clone,
+    M: Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned,
     C: Conditions,
 {
     type Error = Infallible;
 
     async fn message(&self, id: &OperationId) -> Result<Option<M>, Self::Error> {
-        let inner = self.inner.read().await;
-        Ok(inner.messages.get(id).cloned())
+        let Some(bytes) = self.messages.get(id).await? else {
+            return Ok(None);
+        };
+        // `MemoryStore` is only ever fed messages it encoded itself, so a
+        // decode failure here would mean memory corruption, not bad input.
+        Ok(Some(
+            ciborium::from_reader(bytes.as_slice()).expect("stored message is valid CBOR"),
+        ))
     }
 
     async fn set_message(&self, id: &OperationId, message: &M) -> Result<(), Self::Error> {
-        let mut inner = self.inner.write().await;
-        inner.messages.insert(*id, message.clone());
+        let mut buf = Vec::new();
+        ciborium::into_writer(message, &mut buf).expect("message is serializable");
+        self.messages.put(*id, buf).await?;
+        // No receivers is the common case in tests that don't sync; ignore it.
+        let _ = self.notify.send(*id);
         Ok(())
     }
 }
 
+impl<I, M, C> SelectableMessageStore<M> for MemoryStore<I, M, C>
+where
+    I: SpaceId + std::hash::Hash + Eq,
+    M: Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned + SequencedMessage,
+    C: Conditions,
+{
+    async fn select(&self, selector: Selector) -> Result<Vec<M>, Self::Error> {
+        let ids = self.messages.list(KeyRange::all()).await?;
+        let mut matched = Vec::new();
+        for id in ids {
+            if let Some(message) = self.message(&id).await? {
+                if selector.matches(&message) {
+                    matched.push(message);
+                }
+            }
+        }
+        matched.sort_by_key(SequencedMessage::seq_num);
+        Ok(matched)
+    }
+
+    fn subscribe(&self) -> broadcast::Receiver<OperationId> {
+        self.notify.subscribe()
+    }
+}
+
+impl<I, M, C> PrunableMessageStore<M> for MemoryStore<I, M, C>
+where
+    I: SpaceId + std::hash::Hash + Eq,
+    M: Clone + Send + Sync + serde::Serialize + serde::de::DeserializeOwned,
+    C: Conditions,
+{
+    async fn remove_message(&self, id: &OperationId) -> Result<(), Self::Error> {
+        self.messages.rm(id).await
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct TestKeyStore {
     pub(crate) inner: Arc<RwLock<TestKeyStoreInner>>,
@@ -0,0 +1,291 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Drives dealing and recovering a space's threshold-shared secret.
+//!
+//! This sits where `Manager` would call into it: `create_space_threshold`,
+//! `contribute_share` and `recover_secret` are meant to be exposed as
+//! `Manager` methods of the same name, with the coordinator doing the
+//! Shamir math and bookkeeping underneath. `manager.rs` isn't part of this
+//! source tree, so those `Manager` methods don't exist yet; until it is,
+//! this coordinator - exercised directly by the tests below - is as far as
+//! threshold recovery can be wired in from here.
+
+use std::collections::HashMap;
+use std::hash::Hash as StdHash;
+
+use p2panda_encryption::Rng;
+
+use crate::threshold::shamir::{self, SecretCommitment, Share, ThresholdError};
+use crate::threshold::store::ShareStore;
+use crate::types::ActorId;
+
+/// Hands a freshly dealt share to a member over whatever channel the
+/// deployment already uses for confidential delivery (in `p2panda-spaces`,
+/// an encrypted `DirectMessage`). Kept abstract here so this module doesn't
+/// need to depend on `Forge`/`Manager` to send one.
+pub trait ShareTransport<I> {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    async fn send_share(&mut self, space: &I, member: ActorId, share: Share) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ThresholdCoordinatorError<StoreErr, TransportErr> {
+    #[error(transparent)]
+    Threshold(#[from] ThresholdError),
+
+    #[error("share store error: {0}")]
+    Store(StoreErr),
+
+    #[error("share transport error: {0}")]
+    Transport(TransportErr),
+
+    #[error("no commitment on record for this space; it was never dealt in threshold mode")]
+    NotDealt,
+}
+
+pub struct ThresholdCoordinator<I, St, Tr> {
+    shares: St,
+    transport: Tr,
+    rng: Rng,
+    /// Shares a recovering peer has collected towards reconstructing a
+    /// space's secret. Not persisted through `ShareStore`: recovery is a
+    /// short-lived, interactive process driven by the recovering peer, not
+    /// state other members need to see.
+    collected: HashMap<I, Vec<Share>>,
+}
+
+impl<I, St, Tr> ThresholdCoordinator<I, St, Tr>
+where
+    I: Clone + Eq + StdHash,
+    St: ShareStore<I>,
+    Tr: ShareTransport<I>,
+{
+    pub fn new(shares: St, transport: Tr, rng: Rng) -> Self {
+        Self {
+            shares,
+            transport,
+            rng,
+            collected: HashMap::new(),
+        }
+    }
+
+    /// Deal `secret` into shares for `members` with threshold `k`, one share
+    /// per member, and send each member theirs. Always re-deals from
+    /// scratch (clearing any shares and the commitment from a previous
+    /// dealing first), so after a membership change every member holds a
+    /// share of a freshly drawn random polynomial, and an old share does not
+    /// combine with the new ones. Evaluation points are still assigned
+    /// `1..=n` by position in `members`, and `secret` is the caller's: if the
+    /// same secret is dealt again, `k` shares from the previous dealing
+    /// still reconstruct it.
+    pub async fn create_space_threshold(
+        &mut self,
+        id: I,
+        members: &[ActorId],
+        k: u8,
+        secret: &[u8],
+    ) -> Result<(), ThresholdCoordinatorError<St::Error, Tr::Error>> {
+        shamir::validate_members(members, k)?;
+
+        self.shares.clear(&id).await.map_err(ThresholdCoordinatorError::Store)?;
+
+        let dealt = shamir::split(secret, members, k, &self.rng)?;
+        let commitment = SecretCommitment::commit(secret);
+        self.shares
+            .set_commitment(&id, commitment)
+            .await
+            .map_err(ThresholdCoordinatorError::Store)?;
+
+        for (member, share) in dealt {
+            self.shares
+                .set_share(&id, member, share.clone())
+                .await
+                .map_err(ThresholdCoordinatorError::Store)?;
+            self.transport
+                .send_share(&id, member, share)
+                .await
+                .map_err(ThresholdCoordinatorError::Transport)?;
+        }
+
+        Ok(())
+    }
+
+    /// Record a share received from another member towards recovering
+    /// `id`'s secret. Call [`Self::recover_secret`] once enough shares have
+    /// been contributed.
+    ///
+    /// Replaces any previously collected share with the same evaluation
+    /// point instead of appending, so a duplicate delivery of the same
+    /// member's share (retried send, flaky network) never lets the same
+    /// point count twice towards the threshold.
+    pub fn contribute_share(&mut self, id: &I, share: Share) {
+        let shares = self.collected.entry(id.clone()).or_default();
+        match shares.iter_mut().find(|existing| existing.x == share.x) {
+            Some(existing) => *existing = share,
+            None => shares.push(share),
+        }
+    }
+
+    /// Attempt to reconstruct `id`'s secret from contributed shares.
+    /// Returns `Ok(None)` if fewer than `k` shares have been contributed
+    /// yet; returns an error if the space was never dealt in threshold mode
+    /// or if the reconstructed secret fails to match the stored commitment
+    /// (e.g. a contributed share was corrupt or for the wrong dealing).
+    pub async fn recover_secret(
+        &mut self,
+        id: &I,
+        k: u8,
+    ) -> Result<Option<Vec<u8>>, ThresholdCoordinatorError<St::Error, Tr::Error>> {
+        let commitment = self
+            .shares
+            .commitment(id)
+            .await
+            .map_err(ThresholdCoordinatorError::Store)?
+            .ok_or(ThresholdCoordinatorError::NotDealt)?;
+
+        let shares = self.collected.entry(id.clone()).or_default();
+        if shares.len() < k as usize {
+            return Ok(None);
+        }
+
+        Ok(Some(shamir::reconstruct(shares, k, &commitment)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::convert::Infallible;
+
+    use p2panda_core::PrivateKey;
+
+    use super::*;
+    use crate::threshold::store::MemoryShareStore;
+
+    /// Hands each member's share straight back to the test instead of going
+    /// over the network, recording what was sent so it can be fed into
+    /// `contribute_share`.
+    #[derive(Default)]
+    struct RecordingTransport {
+        sent: HashMap<ActorId, Share>,
+    }
+
+    impl ShareTransport<&'static str> for RecordingTransport {
+        type Error = Infallible;
+
+        async fn send_share(
+            &mut self,
+            _space: &&'static str,
+            member: ActorId,
+            share: Share,
+        ) -> Result<(), Self::Error> {
+            self.sent.insert(member, share);
+            Ok(())
+        }
+    }
+
+    fn actor_ids(n: usize, rng: &Rng) -> Vec<ActorId> {
+        (0..n)
+            .map(|_| {
+                let private_key = PrivateKey::from_bytes(&rng.random_array().unwrap());
+                private_key.public_key().into()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn dealing_then_recovering_with_k_shares_round_trips() {
+        let rng = Rng::from_seed([9; 32]);
+        let members = actor_ids(5, &rng);
+        let k = 3;
+        let secret = b"the space's secret".to_vec();
+
+        let mut coordinator = ThresholdCoordinator::new(
+            MemoryShareStore::<&'static str>::new(),
+            RecordingTransport::default(),
+            rng,
+        );
+        coordinator
+            .create_space_threshold("space", &members, k, &secret)
+            .await
+            .unwrap();
+
+        for member in members.iter().take(k as usize) {
+            let share = coordinator.transport.sent.get(member).unwrap().clone();
+            coordinator.contribute_share(&"space", share);
+        }
+
+        let recovered = coordinator
+            .recover_secret(&"space", k)
+            .await
+            .unwrap()
+            .expect("k shares were contributed");
+        assert_eq!(recovered, secret);
+    }
+
+    #[tokio::test]
+    async fn recovering_with_fewer_than_k_shares_returns_none() {
+        let rng = Rng::from_seed([10; 32]);
+        let members = actor_ids(4, &rng);
+        let k = 3;
+        let secret = b"another secret".to_vec();
+
+        let mut coordinator = ThresholdCoordinator::new(
+            MemoryShareStore::<&'static str>::new(),
+            RecordingTransport::default(),
+            rng,
+        );
+        coordinator
+            .create_space_threshold("space", &members, k, &secret)
+            .await
+            .unwrap();
+
+        let share = coordinator
+            .transport
+            .sent
+            .get(&members[0])
+            .unwrap()
+            .clone();
+        coordinator.contribute_share(&"space", share);
+
+        assert_eq!(
+            coordinator.recover_secret(&"space", k).await.unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn duplicate_contribution_does_not_double_count_towards_threshold() {
+        let rng = Rng::from_seed([11; 32]);
+        let members = actor_ids(4, &rng);
+        let k = 3;
+        let secret = b"resilient to retries".to_vec();
+
+        let mut coordinator = ThresholdCoordinator::new(
+            MemoryShareStore::<&'static str>::new(),
+            RecordingTransport::default(),
+            rng,
+        );
+        coordinator
+            .create_space_threshold("space", &members, k, &secret)
+            .await
+            .unwrap();
+
+        let share = coordinator
+            .transport
+            .sent
+            .get(&members[0])
+            .unwrap()
+            .clone();
+        // The same member's share arrives twice (e.g. a retried send);
+        // contributing it again should not count as a second share.
+        coordinator.contribute_share(&"space", share.clone());
+        coordinator.contribute_share(&"space", share);
+
+        assert_eq!(
+            coordinator.recover_secret(&"space", k).await.unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,58 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Arithmetic in GF(2^8), the field classic byte-oriented Shamir secret
+//! sharing (e.g. `ssss`, HashiCorp Vault's unseal keys) operates over. Using
+//! a byte-sized field lets us share a secret of arbitrary length by
+//! splitting each byte independently, instead of having to embed the whole
+//! secret as one big-integer field element.
+
+/// x^8 + x^4 + x^3 + x + 1, the AES/Rijndael reduction polynomial. Any
+/// irreducible polynomial works; this one is reused here because it already
+/// has well-known, validated log/exp tables.
+const REDUCTION_POLY: u16 = 0x11b;
+
+fn build_tables() -> ([u8; 256], [u8; 256]) {
+    let mut exp = [0u8; 256];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    for i in 0..255usize {
+        exp[i] = x as u8;
+        log[x as usize] = i as u8;
+        // Multiply by the generator 0x03 (x + 1): 0x02 is not a generator
+        // under this polynomial and would cycle after 51 steps.
+        x ^= x << 1;
+        if x & 0x100 != 0 {
+            x ^= REDUCTION_POLY;
+        }
+    }
+    // The field has 255 non-zero elements, so the exponent table repeats
+    // with period 255. `mul` and `inv` reduce exponents `% 255` before the
+    // lookup, so index 255 is never read; it is filled to keep the table
+    // periodic.
+    exp[255] = exp[0];
+    (exp, log)
+}
+
+pub fn add(a: u8, b: u8) -> u8 {
+    a ^ b
+}
+
+pub fn mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    let (exp, log) = tables();
+    let sum = log[a as usize] as usize + log[b as usize] as usize;
+    exp[sum % 255]
+}
+
+pub fn inv(a: u8) -> u8 {
+    assert!(a != 0, "zero has no multiplicative inverse in GF(2^8)");
+    let (exp, log) = tables();
+    exp[(255 - log[a as usize] as usize) % 255]
+}
+
+fn tables() -> &'static ([u8; 256], [u8; 256]) {
+    static TABLES: std::sync::OnceLock<([u8; 256], [u8; 256])> = std::sync::OnceLock::new();
+    TABLES.get_or_init(build_tables)
+}
@@ -0,0 +1,21 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Threshold (k-of-N) recovery of a space's encryption secret.
+//!
+//! Normally a space's secret material lives only with the individual
+//! members who hold it through `KeyManager`/`KeySecretStore`: if the
+//! creator is lost, nothing can recover it, and removing a member forces
+//! whoever is online to re-key by hand. This module adds an opt-in mode
+//! where the secret is split with Shamir secret sharing ([`shamir`]) into
+//! one share per member, distributed and kept track of via [`ShareStore`],
+//! and reconstructed by [`ThresholdCoordinator`] once any `k` members
+//! contribute their share.
+
+mod coordinator;
+mod gf256;
+pub mod shamir;
+mod store;
+
+pub use coordinator::{ShareTransport, ThresholdCoordinator, ThresholdCoordinatorError};
+pub use shamir::{SecretCommitment, Share, ThresholdError};
+pub use store::{MemoryShareStore, ShareStore};
@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! k-of-N Shamir secret sharing over GF(2^8), byte by byte.
+
+use p2panda_core::Hash;
+use p2panda_encryption::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::threshold::gf256;
+use crate::types::ActorId;
+
+/// One member's share of a secret. `x` is the member's evaluation point;
+/// `ys` holds the polynomial's value at `x` for every byte of the secret,
+/// so `ys.len() == secret.len()`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Share {
+    pub x: u8,
+    pub ys: Vec<u8>,
+}
+
+/// A commitment to the dealt secret, checked after reconstruction so a
+/// wrong or tampered set of shares is rejected instead of silently handed
+/// back as "the" recovered secret.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretCommitment(Hash);
+
+impl SecretCommitment {
+    pub fn commit(secret: &[u8]) -> Self {
+        Self(Hash::new(secret))
+    }
+
+    pub fn verify(&self, secret: &[u8]) -> bool {
+        self.0 == Hash::new(secret)
+    }
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ThresholdError {
+    #[error("threshold must be at least 1 and at most the number of members (k={k}, n={n})")]
+    InvalidThreshold { k: u8, n: u8 },
+
+    #[error("member list contains a duplicate actor")]
+    DuplicateMember,
+
+    #[error("{n} members exceeds the maximum of {max} evaluation points GF(2^8) can assign", max = u8::MAX)]
+    TooManyMembers { n: usize },
+
+    #[error("need at least {needed} shares to reconstruct, got {got}")]
+    NotEnoughShares { needed: u8, got: usize },
+
+    #[error("shares do not agree on secret length")]
+    MismatchedShareLength,
+
+    #[error("two contributed shares share the same evaluation point")]
+    DuplicateShare,
+
+    #[error("reconstructed secret does not match the stored commitment")]
+    CommitmentMismatch,
+
+    #[error("failed to generate randomness for secret sharing")]
+    Rng,
+}
+
+/// Deal `secret` into `n` shares, any `k` of which reconstruct it. Members
+/// are assigned evaluation points `1..=n` in the order given in `members`;
+/// since membership and ordering must be re-derived identically by every
+/// peer holding a share, callers should always pass members in a
+/// canonical (e.g. sorted) order.
+pub fn split(
+    secret: &[u8],
+    members: &[ActorId],
+    k: u8,
+    rng: &Rng,
+) -> Result<Vec<(ActorId, Share)>, ThresholdError> {
+    validate_members(members, k)?;
+    let unique: std::collections::HashSet<_> = members.iter().collect();
+    if unique.len() != members.len() {
+        return Err(ThresholdError::DuplicateMember);
+    }
+
+    // One length-`k` polynomial per secret byte: coefficients[0] is the
+    // secret byte itself (the polynomial's value at x=0), the rest are
+    // random, making the polynomial degree k-1.
+    let mut coefficients = vec![vec![0u8; k as usize]; secret.len()];
+    for (byte_idx, byte) in secret.iter().enumerate() {
+        coefficients[byte_idx][0] = *byte;
+        for coeff in coefficients[byte_idx].iter_mut().skip(1) {
+            *coeff = rng.random_array::<1>().map_err(|_| ThresholdError::Rng)?[0];
+        }
+    }
+
+    let mut shares = Vec::with_capacity(members.len());
+    for (i, member) in members.iter().enumerate() {
+        let x = (i + 1) as u8;
+        let ys = coefficients
+            .iter()
+            .map(|coeffs| eval_polynomial(coeffs, x))
+            .collect();
+        shares.push((*member, Share { x, ys }));
+    }
+    Ok(shares)
+}
+
+fn eval_polynomial(coefficients: &[u8], x: u8) -> u8 {
+    // Horner's method: evaluate highest-degree term first.
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, &coeff| gf256::add(gf256::mul(acc, x), coeff))
+}
+
+/// Reconstruct the secret from `k` or more shares via Lagrange
+/// interpolation at x=0, then check it against `commitment` before
+/// returning it.
+pub fn reconstruct(
+    shares: &[Share],
+    k: u8,
+    commitment: &SecretCommitment,
+) -> Result<Vec<u8>, ThresholdError> {
+    if shares.len() < k as usize {
+        return Err(ThresholdError::NotEnoughShares {
+            needed: k,
+            got: shares.len(),
+        });
+    }
+    let shares = &shares[..k as usize];
+
+    let unique_points: std::collections::HashSet<_> = shares.iter().map(|share| share.x).collect();
+    if unique_points.len() != shares.len() {
+        return Err(ThresholdError::DuplicateShare);
+    }
+
+    let secret_len = shares[0].ys.len();
+    if shares.iter().any(|share| share.ys.len() != secret_len) {
+        return Err(ThresholdError::MismatchedShareLength);
+    }
+
+    let mut secret = vec![0u8; secret_len];
+    for (idx, out) in secret.iter_mut().enumerate() {
+        *out = lagrange_at_zero(shares, idx);
+    }
+
+    if !commitment.verify(&secret) {
+        return Err(ThresholdError::CommitmentMismatch);
+    }
+    Ok(secret)
+}
+
+fn lagrange_at_zero(shares: &[Share], byte_idx: usize) -> u8 {
+    let mut result = 0u8;
+    for (i, share_i) in shares.iter().enumerate() {
+        // Basis polynomial l_i(0) = product over j != i of (0 - x_j) / (x_i - x_j),
+        // computed in GF(2^8) where subtraction is the same as addition (XOR).
+        let mut numerator = 1u8;
+        let mut denominator = 1u8;
+        for (j, share_j) in shares.iter().enumerate() {
+            if i == j {
+                continue;
+            }
+            numerator = gf256::mul(numerator, share_j.x);
+            denominator = gf256::mul(denominator, gf256::add(share_i.x, share_j.x));
+        }
+        let basis = gf256::mul(numerator, gf256::inv(denominator));
+        result = gf256::add(result, gf256::mul(share_i.ys[byte_idx], basis));
+    }
+    result
+}
+
+pub fn validate_members(members: &[ActorId], k: u8) -> Result<(), ThresholdError> {
+    if members.len() > u8::MAX as usize {
+        return Err(ThresholdError::TooManyMembers { n: members.len() });
+    }
+    // Safe now that `members.len()` is known to fit in a u8: evaluation
+    // points are assigned `1..=n`, and a wrapped `n` here would both
+    // validate `k` against the wrong bound and, in `split`, eventually
+    // collide two members on the same point (x=0, then a repeat of an
+    // earlier point), silently breaking reconstruction instead of failing.
+    let n = members.len() as u8;
+    if k == 0 || k > n {
+        return Err(ThresholdError::InvalidThreshold { k, n });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use p2panda_core::PrivateKey;
+
+    use super::*;
+
+    fn actor_ids(n: usize, rng: &Rng) -> Vec<ActorId> {
+        (0..n)
+            .map(|_| {
+                let private_key = PrivateKey::from_bytes(&rng.random_array().unwrap());
+                private_key.public_key().into()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn split_and_reconstruct_round_trip() {
+        let rng = Rng::from_seed([1; 32]);
+        let secret = b"a space's deep dark secret".to_vec();
+        let members = actor_ids(5, &rng);
+        let k = 3;
+
+        let dealt = split(&secret, &members, k, &rng).unwrap();
+        let commitment = SecretCommitment::commit(&secret);
+
+        let shares: Vec<Share> = dealt
+            .into_iter()
+            .take(k as usize)
+            .map(|(_, share)| share)
+            .collect();
+        let recovered = reconstruct(&shares, k, &commitment).unwrap();
+        assert_eq!(recovered, secret);
+    }
+
+    #[test]
+    fn fewer_than_k_shares_cannot_reconstruct() {
+        let rng = Rng::from_seed([2; 32]);
+        let secret = b"top secret".to_vec();
+        let members = actor_ids(4, &rng);
+        let k = 3;
+
+        let dealt = split(&secret, &members, k, &rng).unwrap();
+        let commitment = SecretCommitment::commit(&secret);
+
+        let shares: Vec<Share> = dealt
+            .into_iter()
+            .take((k - 1) as usize)
+            .map(|(_, share)| share)
+            .collect();
+        assert_eq!(
+            reconstruct(&shares, k, &commitment),
+            Err(ThresholdError::NotEnoughShares {
+                needed: k,
+                got: shares.len(),
+            })
+        );
+    }
+
+    #[test]
+    fn duplicate_evaluation_point_is_rejected_not_panicking() {
+        let rng = Rng::from_seed([3; 32]);
+        let secret = b"duplicate delivery".to_vec();
+        let members = actor_ids(4, &rng);
+        let k = 3;
+
+        let dealt = split(&secret, &members, k, &rng).unwrap();
+        let commitment = SecretCommitment::commit(&secret);
+
+        // Simulate the same member's share arriving twice in place of a
+        // distinct third share.
+        let mut shares: Vec<Share> = dealt
+            .into_iter()
+            .take(2)
+            .map(|(_, share)| share)
+            .collect();
+        shares.push(shares[0].clone());
+
+        assert_eq!(
+            reconstruct(&shares, k, &commitment),
+            Err(ThresholdError::DuplicateShare)
+        );
+    }
+
+    #[test]
+    fn more_than_255_members_is_rejected_instead_of_wrapping() {
+        let rng = Rng::from_seed([5; 32]);
+        let members = actor_ids(u8::MAX as usize + 1, &rng);
+
+        assert_eq!(
+            validate_members(&members, 3),
+            Err(ThresholdError::TooManyMembers {
+                n: members.len()
+            })
+        );
+        assert_eq!(
+            split(b"secret", &members, 3, &rng),
+            Err(ThresholdError::TooManyMembers {
+                n: members.len()
+            })
+        );
+    }
+
+    #[test]
+    fn wrong_commitment_is_rejected() {
+        let rng = Rng::from_seed([4; 32]);
+        let secret = b"correct secret".to_vec();
+        let members = actor_ids(3, &rng);
+        let k = 2;
+
+        let dealt = split(&secret, &members, k, &rng).unwrap();
+        let wrong_commitment = SecretCommitment::commit(b"wrong secret");
+
+        let shares: Vec<Share> = dealt
+            .into_iter()
+            .take(k as usize)
+            .map(|(_, share)| share)
+            .collect();
+        assert_eq!(
+            reconstruct(&shares, k, &wrong_commitment),
+            Err(ThresholdError::CommitmentMismatch)
+        );
+    }
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::hash::Hash as StdHash;
+use std::sync::Arc;
+
+use tokio::sync::RwLock;
+
+use crate::threshold::shamir::{SecretCommitment, Share};
+use crate::types::ActorId;
+
+/// Per-space threshold dealing state: a member's own share plus the
+/// commitment needed to validate a recovered secret, kept alongside
+/// [`KeySecretStore`](crate::traits::KeySecretStore) rather than folded into
+/// it, since most spaces never opt into threshold recovery.
+pub trait ShareStore<I> {
+    type Error: std::error::Error + Send + Sync + 'static;
+
+    async fn share(&self, space: &I, member: &ActorId) -> Result<Option<Share>, Self::Error>;
+
+    async fn set_share(
+        &self,
+        space: &I,
+        member: ActorId,
+        share: Share,
+    ) -> Result<(), Self::Error>;
+
+    async fn commitment(&self, space: &I) -> Result<Option<SecretCommitment>, Self::Error>;
+
+    async fn set_commitment(
+        &self,
+        space: &I,
+        commitment: SecretCommitment,
+    ) -> Result<(), Self::Error>;
+
+    /// Drop every share and the commitment dealt for `space`, so a re-deal
+    /// after a membership change starts from a clean slate.
+    async fn clear(&self, space: &I) -> Result<(), Self::Error>;
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryShareStore<I> {
+    inner: Arc<RwLock<MemoryShareStoreInner<I>>>,
+}
+
+#[derive(Debug)]
+struct MemoryShareStoreInner<I> {
+    shares: HashMap<I, HashMap<ActorId, Share>>,
+    commitments: HashMap<I, SecretCommitment>,
+}
+
+impl<I> MemoryShareStore<I> {
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(MemoryShareStoreInner {
+                shares: HashMap::new(),
+                commitments: HashMap::new(),
+            })),
+        }
+    }
+}
+
+impl<I> Default for MemoryShareStore<I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<I> ShareStore<I> for MemoryShareStore<I>
+where
+    I: Clone + Eq + StdHash + Send + Sync,
+{
+    type Error = Infallible;
+
+    async fn share(&self, space: &I, member: &ActorId) -> Result<Option<Share>, Self::Error> {
+        let inner = self.inner.read().await;
+        Ok(inner
+            .shares
+            .get(space)
+            .and_then(|shares| shares.get(member))
+            .cloned())
+    }
+
+    async fn set_share(&self, space: &I, member: ActorId, share: Share) -> Result<(), Self::Error> {
+        let mut inner = self.inner.write().await;
+        inner
+            .shares
+            .entry(space.clone())
+            .or_default()
+            .insert(member, share);
+        Ok(())
+    }
+
+    async fn commitment(&self, space: &I) -> Result<Option<SecretCommitment>, Self::Error> {
+        let inner = self.inner.read().await;
+        Ok(inner.commitments.get(space).copied())
+    }
+
+    async fn set_commitment(
+        &self,
+        space: &I,
+        commitment: SecretCommitment,
+    ) -> Result<(), Self::Error> {
+        let mut inner = self.inner.write().await;
+        inner.commitments.insert(space.clone(), commitment);
+        Ok(())
+    }
+
+    async fn clear(&self, space: &I) -> Result<(), Self::Error> {
+        let mut inner = self.inner.write().await;
+        inner.shares.remove(space);
+        inner.commitments.remove(space);
+        Ok(())
+    }
+}